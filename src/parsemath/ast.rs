@@ -0,0 +1,19 @@
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Add(Box<Node>, Box<Node>),
+    Subtract(Box<Node>, Box<Node>),
+    Multiply(Box<Node>, Box<Node>),
+    Divide(Box<Node>, Box<Node>),
+    Caret(Box<Node>, Box<Node>),
+    Negative(Box<Node>),
+    Number(f64),
+    Variable(String),
+    Function(String, Vec<Node>),
+    Modulo(Box<Node>, Box<Node>),
+    Equal(Box<Node>, Box<Node>),
+    NotEqual(Box<Node>, Box<Node>),
+    Less(Box<Node>, Box<Node>),
+    Greater(Box<Node>, Box<Node>),
+    LessEqual(Box<Node>, Box<Node>),
+    GreaterEqual(Box<Node>, Box<Node>),
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::ast::Node;
+
+#[derive(Debug, PartialEq)]
+pub enum EvaluationError {
+    DivisionByZero,
+    UndefinedVariable(String),
+    UnknownFunction(String),
+    WrongArgumentCount(String, usize),
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvaluationError::DivisionByZero => write!(f, "division by zero"),
+            EvaluationError::UndefinedVariable(name) => {
+                write!(f, "undefined variable `{}`", name)
+            }
+            EvaluationError::UnknownFunction(name) => write!(f, "unknown function `{}`", name),
+            EvaluationError::WrongArgumentCount(name, found) => {
+                write!(f, "`{}` takes exactly one argument, got {}", name, found)
+            }
+        }
+    }
+}
+
+impl Error for EvaluationError {}
+
+pub fn eval(expr: &Node, env: &HashMap<String, f64>) -> Result<f64, EvaluationError> {
+    use self::Node::*;
+
+    match expr {
+        Number(i) => Ok(*i),
+        Variable(name) => env
+            .get(name)
+            .copied()
+            .ok_or_else(|| EvaluationError::UndefinedVariable(name.clone())),
+        Add(left, right) => Ok(eval(left, env)? + eval(right, env)?),
+        Subtract(left, right) => Ok(eval(left, env)? - eval(right, env)?),
+        Multiply(left, right) => Ok(eval(left, env)? * eval(right, env)?),
+        Divide(left, right) => {
+            let right = eval(right, env)?;
+            if right == 0.0 {
+                return Err(EvaluationError::DivisionByZero);
+            }
+            Ok(eval(left, env)? / right)
+        }
+        Caret(base, exp) => Ok(eval(base, env)?.powf(eval(exp, env)?)),
+        Negative(value) => Ok(-eval(value, env)?),
+        Function(name, args) => {
+            let [arg] = args.as_slice() else {
+                return Err(EvaluationError::WrongArgumentCount(
+                    name.clone(),
+                    args.len(),
+                ));
+            };
+            let arg = eval(arg, env)?;
+            match name.as_str() {
+                "sin" => Ok(arg.sin()),
+                "cos" => Ok(arg.cos()),
+                "tan" => Ok(arg.tan()),
+                "sqrt" => Ok(arg.sqrt()),
+                "abs" => Ok(arg.abs()),
+                "ln" => Ok(arg.ln()),
+                "log10" => Ok(arg.log10()),
+                "exp" => Ok(arg.exp()),
+                _ => Err(EvaluationError::UnknownFunction(name.clone())),
+            }
+        }
+        Modulo(left, right) => {
+            let right = eval(right, env)?;
+            if right == 0.0 {
+                return Err(EvaluationError::DivisionByZero);
+            }
+            Ok(eval(left, env)?.rem_euclid(right))
+        }
+        Equal(left, right) => Ok(truthy(eval(left, env)? == eval(right, env)?)),
+        NotEqual(left, right) => Ok(truthy(eval(left, env)? != eval(right, env)?)),
+        Less(left, right) => Ok(truthy(eval(left, env)? < eval(right, env)?)),
+        Greater(left, right) => Ok(truthy(eval(left, env)? > eval(right, env)?)),
+        LessEqual(left, right) => Ok(truthy(eval(left, env)? <= eval(right, env)?)),
+        GreaterEqual(left, right) => Ok(truthy(eval(left, env)? >= eval(right, env)?)),
+    }
+}
+
+fn truthy(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsemath::parser::Parser;
+
+    fn eval_str(expr: &str, env: &HashMap<String, f64>) -> Result<f64, EvaluationError> {
+        let mut parser = Parser::new(expr).unwrap();
+        let ast = parser.parse().unwrap();
+        eval(&ast, env)
+    }
+
+    fn no_vars() -> HashMap<String, f64> {
+        HashMap::new()
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(eval_str("1+2", &no_vars()), Ok(3.0));
+    }
+
+    #[test]
+    fn test_subtract() {
+        assert_eq!(eval_str("5-2", &no_vars()), Ok(3.0));
+    }
+
+    #[test]
+    fn test_multiply() {
+        assert_eq!(eval_str("3*4", &no_vars()), Ok(12.0));
+    }
+
+    #[test]
+    fn test_divide() {
+        assert_eq!(eval_str("10/2", &no_vars()), Ok(5.0));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(
+            eval_str("1/0", &no_vars()),
+            Err(EvaluationError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_caret() {
+        assert_eq!(eval_str("2^3", &no_vars()), Ok(8.0));
+    }
+
+    #[test]
+    fn test_negative() {
+        assert_eq!(eval_str("-4", &no_vars()), Ok(-4.0));
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        assert_eq!(eval_str("1+2*3", &no_vars()), Ok(7.0));
+    }
+
+    #[test]
+    fn test_variable_substitution() {
+        let mut env = HashMap::new();
+        env.insert("pi".to_string(), std::f64::consts::PI);
+        env.insert("r".to_string(), 2.0);
+        assert_eq!(
+            eval_str("2*pi*r", &env),
+            Ok(2.0 * std::f64::consts::PI * 2.0)
+        );
+    }
+
+    #[test]
+    fn test_function_call() {
+        assert_eq!(eval_str("sqrt(16)", &no_vars()), Ok(4.0));
+    }
+
+    #[test]
+    fn test_function_call_with_expression_argument() {
+        assert_eq!(eval_str("abs(2-5)", &no_vars()), Ok(3.0));
+    }
+
+    #[test]
+    fn test_function_wrong_argument_count() {
+        let call = Node::Function("sin".to_string(), vec![]);
+        assert_eq!(
+            eval(&call, &no_vars()),
+            Err(EvaluationError::WrongArgumentCount("sin".to_string(), 0))
+        );
+    }
+
+    #[test]
+    fn test_unknown_function() {
+        assert_eq!(
+            eval_str("frobnicate(1)", &no_vars()),
+            Err(EvaluationError::UnknownFunction("frobnicate".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_modulo() {
+        assert_eq!(eval_str("7%2", &no_vars()), Ok(1.0));
+    }
+
+    #[test]
+    fn test_modulo_by_zero() {
+        assert_eq!(
+            eval_str("7%0", &no_vars()),
+            Err(EvaluationError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_comparisons_produce_truthiness() {
+        assert_eq!(eval_str("1==1", &no_vars()), Ok(1.0));
+        assert_eq!(eval_str("1!=1", &no_vars()), Ok(0.0));
+        assert_eq!(eval_str("1<2", &no_vars()), Ok(1.0));
+        assert_eq!(eval_str("1>2", &no_vars()), Ok(0.0));
+        assert_eq!(eval_str("2<=2", &no_vars()), Ok(1.0));
+        assert_eq!(eval_str("2>=3", &no_vars()), Ok(0.0));
+    }
+
+    #[test]
+    fn test_conditional_formula() {
+        let mut env = HashMap::new();
+        env.insert("a".to_string(), 3.0);
+        env.insert("b".to_string(), 5.0);
+        assert_eq!(eval_str("(a>b)*a+(a<=b)*b", &env), Ok(5.0));
+    }
+
+    #[test]
+    fn test_undefined_variable() {
+        assert_eq!(
+            eval_str("x+1", &no_vars()),
+            Err(EvaluationError::UndefinedVariable("x".to_string()))
+        );
+    }
+}
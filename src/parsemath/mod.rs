@@ -0,0 +1,5 @@
+pub mod ast;
+pub mod eval;
+pub mod parser;
+pub mod token;
+pub mod tokenizer;
@@ -1,114 +1,170 @@
 use super::ast::Node;
-use super::token::{OperPrec, Token};
-use super::tokenizer::Tokenizer;
+use super::token::Token;
+use super::tokenizer::{Span, Tokenizer};
+
+// Binding powers for the Pratt parser below. Each infix operator gets a
+// `(left_bp, right_bp)` pair; `parse_expr` stops consuming once an operator's
+// left_bp drops below the minimum it was called with, and recurses into the
+// right-hand side with that operator's right_bp as the new minimum. Making
+// right_bp *lower* than left_bp (as `CARET_BP` does) yields right
+// associativity; the usual `right_bp = left_bp + 1` yields left associativity.
+const COMPARISON_BP: (u8, u8) = (2, 3);
+const ADD_SUB_BP: (u8, u8) = (4, 5);
+const MUL_DIV_MOD_BP: (u8, u8) = (6, 7);
+const CARET_BP: (u8, u8) = (10, 9);
+
+// Unary minus has no left_bp of its own (there is nothing to its left), only
+// a right_bp for parsing its operand. It binds tighter than `^`'s left_bp so
+// that `-1^2` parses as `(-1)^2`, matching this crate's original precedence.
+const NEGATIVE_BP: u8 = 11;
+
+fn infix_binding_power(token: &Token) -> Option<(u8, u8)> {
+    match token {
+        Token::Equal
+        | Token::NotEqual
+        | Token::Less
+        | Token::Greater
+        | Token::LessEqual
+        | Token::GreaterEqual => Some(COMPARISON_BP),
+        Token::Add | Token::Subtract => Some(ADD_SUB_BP),
+        Token::Multiply | Token::Divide | Token::Modulo => Some(MUL_DIV_MOD_BP),
+        Token::Caret => Some(CARET_BP),
+        _ => None,
+    }
+}
+
+fn prefix_binding_power(token: &Token) -> Option<u8> {
+    match token {
+        Token::Subtract => Some(NEGATIVE_BP),
+        _ => None,
+    }
+}
 
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
+    current_span: Span,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(expr: &'a str) -> Result<Self, ParseError> {
         let mut lexer = Tokenizer::new(expr);
-        let cur_token = match lexer.next() {
-            Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+        let (cur_token, cur_span) = match lexer.next() {
+            Some(pair) => pair,
+            None => {
+                return Err(ParseError::InvalidOperator(
+                    "Invalid character".into(),
+                    Span::new(0, 0),
+                ))
+            }
         };
         Ok(Parser {
             tokenizer: lexer,
             current_token: cur_token,
+            current_span: cur_span,
         })
     }
 
     pub fn parse(&mut self) -> Result<Node, ParseError> {
-        let ast = self.generate_ast(OperPrec::DefaultZero);
-        match ast {
-            Ok(ast) => Ok(ast),
-            Err(e) => Err(e),
-        }
+        self.parse_expr(0)
     }
 }
 
 impl<'a> Parser<'a> {
     fn get_next_token(&mut self) -> Result<(), ParseError> {
-        let next_token = match self.tokenizer.next() {
-            Some(token) => token,
-            None => return Err(ParseError::InvalidOperator("Invalid character".into())),
+        let (next_token, next_span) = match self.tokenizer.next() {
+            Some(pair) => pair,
+            None => {
+                return Err(ParseError::InvalidOperator(
+                    "Invalid character".into(),
+                    self.current_span,
+                ))
+            }
         };
         self.current_token = next_token;
+        self.current_span = next_span;
         Ok(())
     }
 
-    fn generate_ast(&mut self, oper_prec: OperPrec) -> Result<Node, ParseError> {
-        let mut left_expr = self.parse_number()?;
+    /// Parses one expression, consuming infix operators whose left_bp is at
+    /// least `min_bp`. Adding an infix operator means giving it a binding power
+    /// in `infix_binding_power` and a `Node` in the match below (a prefix
+    /// operator goes in `prefix_binding_power` and `parse_atom` instead).
+    fn parse_expr(&mut self, min_bp: u8) -> Result<Node, ParseError> {
+        let mut lhs = self.parse_atom()?;
 
-        while oper_prec < self.current_token.get_oper_prec() {
-            if self.current_token == Token::EOF {
+        while let Some((left_bp, right_bp)) = infix_binding_power(&self.current_token) {
+            if left_bp < min_bp {
                 break;
             }
-            let added_expr = self.convert_token_to_node(left_expr.clone())?;
-            left_expr = added_expr;
+
+            let op = self.current_token.clone();
+            self.get_next_token()?;
+            let rhs = self.parse_expr(right_bp)?;
+            lhs = match op {
+                Token::Add => Node::Add(Box::new(lhs), Box::new(rhs)),
+                Token::Subtract => Node::Subtract(Box::new(lhs), Box::new(rhs)),
+                Token::Multiply => Node::Multiply(Box::new(lhs), Box::new(rhs)),
+                Token::Divide => Node::Divide(Box::new(lhs), Box::new(rhs)),
+                Token::Caret => Node::Caret(Box::new(lhs), Box::new(rhs)),
+                Token::Modulo => Node::Modulo(Box::new(lhs), Box::new(rhs)),
+                Token::Equal => Node::Equal(Box::new(lhs), Box::new(rhs)),
+                Token::NotEqual => Node::NotEqual(Box::new(lhs), Box::new(rhs)),
+                Token::Less => Node::Less(Box::new(lhs), Box::new(rhs)),
+                Token::Greater => Node::Greater(Box::new(lhs), Box::new(rhs)),
+                Token::LessEqual => Node::LessEqual(Box::new(lhs), Box::new(rhs)),
+                Token::GreaterEqual => Node::GreaterEqual(Box::new(lhs), Box::new(rhs)),
+                _ => unreachable!("infix_binding_power only returns Some for these tokens"),
+            };
         }
-        Ok(left_expr)
+
+        Ok(lhs)
     }
 
-    fn parse_number(&mut self) -> Result<Node, ParseError> {
+    /// Parses a prefix operator or a leaf: a number, a variable, a function
+    /// call, or a parenthesized expression.
+    fn parse_atom(&mut self) -> Result<Node, ParseError> {
         let token = self.current_token.clone();
         match token {
             Token::Subtract => {
                 self.get_next_token()?;
-                let expr = self.generate_ast(OperPrec::Negative)?;
+                let right_bp = prefix_binding_power(&Token::Subtract).unwrap();
+                let expr = self.parse_expr(right_bp)?;
                 Ok(Node::Negative(Box::new(expr)))
             }
             Token::Num(i) => {
                 self.get_next_token()?;
                 Ok(Node::Number(i))
             }
+            Token::Ident(name) => {
+                self.get_next_token()?;
+                // An identifier directly followed by `(` is a function call, so
+                // `sin(x)` becomes a call rather than a variable. Implicit
+                // multiplication (the `LeftParen` branch below) only applies after
+                // a closing paren, never after an identifier.
+                if self.current_token == Token::LeftParen {
+                    self.get_next_token()?;
+                    let arg = self.parse_expr(0)?;
+                    self.check_paren()?;
+                    Ok(Node::Function(name, vec![arg]))
+                } else {
+                    Ok(Node::Variable(name))
+                }
+            }
             Token::LeftParen => {
                 self.get_next_token()?;
-                let expr = self.generate_ast(OperPrec::DefaultZero)?;
+                let expr = self.parse_expr(0)?;
                 self.check_paren()?;
                 if self.current_token == Token::LeftParen {
-                    let right = self.generate_ast(OperPrec::MulDiv)?;
+                    let right = self.parse_expr(MUL_DIV_MOD_BP.1)?;
                     return Ok(Node::Multiply(Box::new(expr), Box::new(right)));
                 }
                 Ok(expr)
             }
-            _ => Err(ParseError::UnableToParse("Unable to parse".to_string())),
-        }
-    }
-
-    fn convert_token_to_node(&mut self, left_expr: Node) -> Result<Node, ParseError> {
-        match self.current_token {
-            Token::Add => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::AddSub)?;
-                Ok(Node::Add(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Subtract => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::AddSub)?;
-                Ok(Node::Subtract(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Multiply => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::MulDiv)?;
-                Ok(Node::Multiply(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Divide => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::MulDiv)?;
-                Ok(Node::Divide(Box::new(left_expr), Box::new(right_expr)))
-            }
-            Token::Caret => {
-                self.get_next_token()?;
-                let right_expr = self.generate_ast(OperPrec::Power)?;
-                Ok(Node::Caret(Box::new(left_expr), Box::new(right_expr)))
-            }
-            _ => Err(ParseError::InvalidOperator(format!(
-                "Please enter valid operator {:?}",
-                self.current_token
-            ))),
+            _ => Err(ParseError::UnableToParse(
+                "Unable to parse".to_string(),
+                self.current_span,
+            )),
         }
     }
 
@@ -117,19 +173,22 @@ impl<'a> Parser<'a> {
             self.get_next_token()?;
             Ok(())
         } else {
-            Err(ParseError::InvalidOperator(format!(
-                "Expected {:?}, got {:?}",
-                Token::RightParen,
-                self.current_token
-            )))
+            Err(ParseError::InvalidOperator(
+                format!(
+                    "Expected {:?}, got {:?}",
+                    Token::RightParen,
+                    self.current_token
+                ),
+                self.current_span,
+            ))
         }
     }
 }
 
 #[derive(Debug)]
 pub enum ParseError {
-    UnableToParse(String),
-    InvalidOperator(String),
+    UnableToParse(String, Span),
+    InvalidOperator(String, Span),
 }
 
 #[cfg(test)]
@@ -226,6 +285,16 @@ mod tests {
         assert_eq!(parser.parse().unwrap(), expected)
     }
 
+    #[test]
+    fn test_power_right_associative() {
+        let mut parser = Parser::new("2^3^2").unwrap();
+        let expected = Caret(
+            Box::new(Number(2.0)),
+            Box::new(Caret(Box::new(Number(3.0)), Box::new(Number(2.0)))),
+        );
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
     #[test]
     fn test_multiplication_of_parentheses() {
         let mut parser = Parser::new("(1+2)(3+4)").unwrap();
@@ -235,4 +304,83 @@ mod tests {
         );
         assert_eq!(parser.parse().unwrap(), expected)
     }
+
+    #[test]
+    fn test_variable() {
+        let mut parser = Parser::new("2*pi*r").unwrap();
+        let expected = Multiply(
+            Box::new(Multiply(
+                Box::new(Number(2.0)),
+                Box::new(Variable("pi".to_string())),
+            )),
+            Box::new(Variable("r".to_string())),
+        );
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
+    #[test]
+    fn test_function_call() {
+        let mut parser = Parser::new("sin(x)").unwrap();
+        let expected = Function("sin".to_string(), vec![Variable("x".to_string())]);
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
+    #[test]
+    fn test_function_call_with_expression_argument() {
+        let mut parser = Parser::new("sqrt(1+2)").unwrap();
+        let expected = Function(
+            "sqrt".to_string(),
+            vec![Add(Box::new(Number(1.0)), Box::new(Number(2.0)))],
+        );
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
+    #[test]
+    fn test_modulo() {
+        let mut parser = Parser::new("7%2").unwrap();
+        let expected = Modulo(Box::new(Number(7.0)), Box::new(Number(2.0)));
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let test_set = vec![
+            ("1==2", Equal(Box::new(Number(1.0)), Box::new(Number(2.0)))),
+            ("1!=2", NotEqual(Box::new(Number(1.0)), Box::new(Number(2.0)))),
+            ("1<2", Less(Box::new(Number(1.0)), Box::new(Number(2.0)))),
+            ("1>2", Greater(Box::new(Number(1.0)), Box::new(Number(2.0)))),
+            ("1<=2", LessEqual(Box::new(Number(1.0)), Box::new(Number(2.0)))),
+            (
+                "1>=2",
+                GreaterEqual(Box::new(Number(1.0)), Box::new(Number(2.0))),
+            ),
+        ];
+
+        for (expr, expected) in test_set.into_iter() {
+            let mut parser = Parser::new(expr).unwrap();
+            assert_eq!(parser.parse().unwrap(), expected)
+        }
+    }
+
+    #[test]
+    fn test_comparison_binds_looser_than_arithmetic() {
+        let mut parser = Parser::new("a>b*2").unwrap();
+        let expected = Greater(
+            Box::new(Variable("a".to_string())),
+            Box::new(Multiply(
+                Box::new(Variable("b".to_string())),
+                Box::new(Number(2.0)),
+            )),
+        );
+        assert_eq!(parser.parse().unwrap(), expected)
+    }
+
+    #[test]
+    fn test_error_carries_span() {
+        let mut parser = Parser::new("1+").unwrap();
+        match parser.parse() {
+            Err(ParseError::UnableToParse(_, span)) => assert_eq!(span, Span::new(2, 2)),
+            other => panic!("expected UnableToParse with a span, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,20 @@
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Caret,
+    LeftParen,
+    RightParen,
+    Num(f64),
+    Ident(String),
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    EOF,
+}
@@ -3,47 +3,115 @@ use std::str::Chars;
 
 use super::token::Token;
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// Renders `input` with a line of carets underlining `span`, for diagnostics.
+pub fn render_snippet(input: &str, span: &Span) -> String {
+    let end = span.end.max(span.start + 1);
+    let carets: String = input
+        .chars()
+        .enumerate()
+        .map(|(i, _)| if i >= span.start && i < end { '^' } else { ' ' })
+        .collect();
+    format!("{}\n{}", input, carets)
+}
+
 pub struct Tokenizer<'a> {
     expr: Peekable<Chars<'a>>,
+    pos: usize,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(new_expr: &'a str) -> Self {
         Tokenizer {
             expr: new_expr.chars().peekable(),
+            pos: 0,
         }
     }
 }
 
 impl<'a> Iterator for Tokenizer<'a> {
-    type Item = Token;
+    type Item = (Token, Span);
 
     fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
         let next_char = self.expr.next();
+        if next_char.is_some() {
+            self.pos += 1;
+        }
 
-        match next_char {
+        let token = match next_char {
             Some('0'..='9') => {
                 let mut number = next_char?.to_string();
 
                 while let Some(next_char) = self.expr.peek() {
                     if next_char.is_numeric() || next_char == &'.' {
                         number.push(self.expr.next()?);
+                        self.pos += 1;
                     } else {
                         break;
                     }
                 }
-                Some(Token::Num(number.parse::<f64>().unwrap()))
+                Token::Num(number.parse::<f64>().unwrap())
             }
-            Some('+') => Some(Token::Add),
-            Some('-') => Some(Token::Subtract),
-            Some('*') => Some(Token::Multiply),
-            Some('/') => Some(Token::Divide),
-            Some('^') => Some(Token::Caret),
-            Some('(') => Some(Token::LeftParen),
-            Some(')') => Some(Token::RightParen),
-            None => Some(Token::EOF),
-            Some(_) => None,
-        }
+            Some('+') => Token::Add,
+            Some('-') => Token::Subtract,
+            Some('*') => Token::Multiply,
+            Some('/') => Token::Divide,
+            Some('^') => Token::Caret,
+            Some('%') => Token::Modulo,
+            Some('<') if self.expr.peek() == Some(&'=') => {
+                self.expr.next();
+                self.pos += 1;
+                Token::LessEqual
+            }
+            Some('>') if self.expr.peek() == Some(&'=') => {
+                self.expr.next();
+                self.pos += 1;
+                Token::GreaterEqual
+            }
+            Some('<') => Token::Less,
+            Some('>') => Token::Greater,
+            Some('=') if self.expr.peek() == Some(&'=') => {
+                self.expr.next();
+                self.pos += 1;
+                Token::Equal
+            }
+            Some('!') if self.expr.peek() == Some(&'=') => {
+                self.expr.next();
+                self.pos += 1;
+                Token::NotEqual
+            }
+            Some('(') => Token::LeftParen,
+            Some(')') => Token::RightParen,
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_string();
+
+                while let Some(next_char) = self.expr.peek() {
+                    if next_char.is_alphanumeric() || next_char == &'_' {
+                        ident.push(self.expr.next()?);
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(ident)
+            }
+            None => Token::EOF,
+            Some(_) => return None,
+        };
+
+        Some((token, Span::new(start, self.pos)))
     }
 }
 
@@ -54,13 +122,13 @@ mod tests {
     #[test]
     fn test_positive_integer() {
         let mut tokenizer = Tokenizer::new("42");
-        assert_eq!(tokenizer.next().unwrap(), Token::Num(42.0))
+        assert_eq!(tokenizer.next().unwrap(), (Token::Num(42.0), Span::new(0, 2)))
     }
 
     #[test]
     fn test_decimal_integer() {
         let mut tokenizer = Tokenizer::new("42.1");
-        assert_eq!(tokenizer.next().unwrap(), Token::Num(42.1))
+        assert_eq!(tokenizer.next().unwrap(), (Token::Num(42.1), Span::new(0, 4)))
     }
 
     #[test]
@@ -75,7 +143,7 @@ mod tests {
 
         for (str, token) in test_set.into_iter() {
             let mut tokenizer = Tokenizer::new(str);
-            assert_eq!(tokenizer.next().unwrap(), token)
+            assert_eq!(tokenizer.next().unwrap(), (token, Span::new(0, 1)))
         }
     }
 
@@ -85,19 +153,80 @@ mod tests {
 
         for (str, token) in test_set.into_iter() {
             let mut tokenizer = Tokenizer::new(str);
-            assert_eq!(tokenizer.next().unwrap(), token)
+            assert_eq!(tokenizer.next().unwrap(), (token, Span::new(0, 1)))
         }
     }
 
     #[test]
     fn test_eof() {
         let mut tokenizer = Tokenizer::new("");
-        assert_eq!(tokenizer.next().unwrap(), Token::EOF)
+        assert_eq!(tokenizer.next().unwrap(), (Token::EOF, Span::new(0, 0)))
     }
 
     #[test]
     fn test_invalid_char() {
-        let mut tokenizer = Tokenizer::new("a");
+        let mut tokenizer = Tokenizer::new("@");
         assert_eq!(tokenizer.next(), None)
     }
+
+    #[test]
+    fn test_identifier() {
+        let mut tokenizer = Tokenizer::new("pi");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            (Token::Ident("pi".to_string()), Span::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn test_identifier_with_digits_and_underscore() {
+        let mut tokenizer = Tokenizer::new("r_2");
+        assert_eq!(
+            tokenizer.next().unwrap(),
+            (Token::Ident("r_2".to_string()), Span::new(0, 3))
+        );
+    }
+
+    #[test]
+    fn test_spans_advance_across_tokens() {
+        let mut tokenizer = Tokenizer::new("12+3");
+        assert_eq!(tokenizer.next().unwrap(), (Token::Num(12.0), Span::new(0, 2)));
+        assert_eq!(tokenizer.next().unwrap(), (Token::Add, Span::new(2, 3)));
+        assert_eq!(tokenizer.next().unwrap(), (Token::Num(3.0), Span::new(3, 4)));
+    }
+
+    #[test]
+    fn test_render_snippet() {
+        let rendered = render_snippet("1+a", &Span::new(2, 3));
+        assert_eq!(rendered, "1+a\n  ^");
+    }
+
+    #[test]
+    fn test_modulo_and_single_char_comparisons() {
+        let test_set = vec![
+            ("%", Token::Modulo),
+            ("<", Token::Less),
+            (">", Token::Greater),
+        ];
+
+        for (str, token) in test_set.into_iter() {
+            let mut tokenizer = Tokenizer::new(str);
+            assert_eq!(tokenizer.next().unwrap(), (token, Span::new(0, 1)))
+        }
+    }
+
+    #[test]
+    fn test_two_char_comparisons() {
+        let test_set = vec![
+            ("==", Token::Equal),
+            ("!=", Token::NotEqual),
+            ("<=", Token::LessEqual),
+            (">=", Token::GreaterEqual),
+        ];
+
+        for (str, token) in test_set.into_iter() {
+            let mut tokenizer = Tokenizer::new(str);
+            assert_eq!(tokenizer.next().unwrap(), (token, Span::new(0, 2)))
+        }
+    }
 }